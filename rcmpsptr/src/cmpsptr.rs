@@ -5,69 +5,180 @@ This Source Code Form is subject to the terms of the Mozilla Public
 License, v. 2.0. If a copy of the MPL was not distributed with this
 file, You can obtain one at https://mozilla.org/MPL/2.0/.
 */
+#![cfg_attr(not(feature = "std"), no_std)]
+// Needed for the DST pointers (`CmpsFatPtr`, `CmpsUnqDst`, `CmpsCntDst`) to
+// rebuild a fat pointer from the compressed address plus stored metadata, and
+// to accept `new_unsize`'s coercion. Both are unstable, so nightly only.
+#![feature(ptr_metadata, unsize)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+// `ptr`/`ptr_mut` take `&self` by design: uniqueness is enforced by the owner
+// (move semantics, refcounts, or caller discipline), not the borrow checker.
+#[allow(clippy::mut_from_ref, clippy::new_without_default)]
 pub mod cmpsptr {
+    #[cfg(feature = "std")]
     use std::vec::Vec;
-    use std::marker::PhantomData;
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use core::cell::Cell;
+    use core::marker::{PhantomData, Unsize};
+    use core::mem::ManuallyDrop;
+    use core::num::NonZeroU32;
+    use core::ops::{Deref, DerefMut};
+    use core::ptr::{self, copy_nonoverlapping, drop_in_place, NonNull, Pointee};
+    use core::sync::atomic::{fence, AtomicU32, AtomicUsize, Ordering};
+    #[cfg(not(feature = "std"))]
+    use core::sync::atomic::AtomicBool;
+
+    #[cfg(feature = "std")]
     use lazy_static::lazy_static;
-    use std::ops::{Deref, DerefMut};
-    use std::ptr::copy_nonoverlapping;
+    #[cfg(feature = "std")]
     use std::sync::{Mutex, MutexGuard};
-    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[cfg(feature = "std")]
     use std::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+    #[cfg(not(feature = "std"))]
+    use alloc::alloc::{alloc, dealloc, handle_alloc_error};
+    #[cfg(not(feature = "std"))]
+    use core::alloc::Layout;
+
+    // Backing store for listing mode: the index handed out for a pointer is 1-based
+    // and the pointer lives at `slots[idx - 1]` (so 0 is never handed out, which is
+    // what lets the returned index be a `NonZeroU32`), and `free` is a stack of
+    // vacated 0-based `slots` positions available for O(1) reuse. Every position in
+    // `0..slots.len()` is present in exactly one of {a live, non-zero `slots` entry,
+    // `free`} at any time; `list` and `unlist` are the only ways to move a position
+    // between those two states.
+    struct PtrTable {
+        slots: Vec<usize>,
+        free: Vec<u32>,
+    }
+
+    impl PtrTable {
+        #[inline(always)]
+        fn list(&mut self, ptr: usize) -> NonZeroU32 {
+            if let Some(idx) = self.free.pop() {
+                self.slots[idx as usize] = ptr;
+                unsafe { NonZeroU32::new_unchecked(idx + 1) }
+            } else {
+                self.slots.push(ptr);
+                unsafe { NonZeroU32::new_unchecked(self.slots.len() as u32) }
+            }
+        }
+
+        #[inline(always)]
+        fn unlist(&mut self, idx: NonZeroU32) {
+            let i = idx.get() - 1;
+            self.slots[i as usize] = 0;
+            self.free.push(i);
+        }
+    }
 
+    #[cfg(feature = "std")]
     lazy_static! {
-        static ref _PTR_LIST: Mutex<Vec<usize>> = Mutex::new(vec![]);
+        static ref _PTR_LIST: Mutex<PtrTable> = Mutex::new(PtrTable { slots: vec![], free: vec![] });
     }
 
-    static mut _GLOBAL_NEW_MASK: usize = usize::MAX;
-    static mut _GLOBAL_MASK: usize = usize::MAX;
-    static mut _NULL_IDX: usize = 0;
+    // `no_std` has neither `lazy_static` nor `std::sync::Mutex`, so the listed-pointer
+    // table is protected by a minimal spin-lock instead: a CAS loop over this flag
+    // guards exclusive access to the static `PtrTable` below.
+    #[cfg(not(feature = "std"))]
+    static _PTR_LIST_LOCK: AtomicBool = AtomicBool::new(false);
+    #[cfg(not(feature = "std"))]
+    static mut _PTR_LIST: PtrTable = PtrTable { slots: Vec::new(), free: Vec::new() };
+
+    // `usize::MAX` marks "not yet established"; the first caller to see that value
+    // wins the `compare_exchange` in `compress!` and fixes the mask for every
+    // address region sharing those high bits, so concurrent compression of
+    // same-region pointers from different threads agrees on one mask.
+    static _GLOBAL_NEW_MASK: AtomicUsize = AtomicUsize::new(usize::MAX);
+    static _GLOBAL_MASK: AtomicUsize = AtomicUsize::new(usize::MAX);
 
     #[inline(always)]
     fn listed(ptr: usize) -> bool {
         (ptr & 1) == 1
     }
 
+    #[cfg(feature = "std")]
     #[inline(always)]
-    fn ptr_list() -> MutexGuard<'static, Vec<usize>> {
+    fn ptr_list() -> MutexGuard<'static, PtrTable> {
         _PTR_LIST.lock().unwrap_or_else(|e| {
             panic!("CANNOT UNWRAP POINTER LIST: {}", e);
         })
     }
 
-    fn list_ptr<const LIST_ONLY: bool>(ptr: usize) -> u32 {
-        unsafe {
-            let mut mutex_list = ptr_list();
-            let ptr_list = mutex_list.deref_mut();
-            let mut list_len = ptr_list.len();
-            for mut i in _NULL_IDX..list_len {
-                if ptr_list[i] == 0 {
-                    ptr_list[i] = ptr;
-                    i += 1; _NULL_IDX = i;
-                    if LIST_ONLY {
-                        return i as u32;
-                    }
-                    return ((i << 1) | 1) as u32;
-                }
-            }
-            list_len += 1;
-            ptr_list.push(ptr);
-            _NULL_IDX = list_len;
-            if LIST_ONLY {
-                list_len as u32
-            } else {
-                ((list_len << 1) | 1) as u32
-            }
+    // Stands in for `MutexGuard` on `no_std`: releases the spin-lock on drop so
+    // callers can keep treating `ptr_list()` as a guarded `PtrTable` either way.
+    #[cfg(not(feature = "std"))]
+    struct PtrListGuard {
+        _private: (),
+    }
+
+    #[cfg(not(feature = "std"))]
+    impl Deref for PtrListGuard {
+        type Target = PtrTable;
+        #[inline(always)]
+        fn deref(&self) -> &PtrTable {
+            unsafe { &*core::ptr::addr_of!(_PTR_LIST) }
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    impl DerefMut for PtrListGuard {
+        #[inline(always)]
+        fn deref_mut(&mut self) -> &mut PtrTable {
+            unsafe { &mut *core::ptr::addr_of_mut!(_PTR_LIST) }
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    impl Drop for PtrListGuard {
+        #[inline(always)]
+        fn drop(&mut self) {
+            _PTR_LIST_LOCK.store(false, Ordering::Release);
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[inline(always)]
+    fn ptr_list() -> PtrListGuard {
+        while _PTR_LIST_LOCK
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        PtrListGuard { _private: () }
+    }
+
+    // The `|1` tag this sets (for `LIST_ONLY = false`) is read back by `listed()`
+    // in `apply_global_mask` off the *reconstructed* pointer, which `get_ptr`
+    // left-shifts by `cmps_level::<CMPS_LEVEL>()` first. That shift only
+    // preserves bit 0 when it's zero, i.e. at `CMPS_LEVEL == 1`; at
+    // `CMPS_LEVEL >= 2` the tag is shifted away and a mask-mismatched pointer
+    // silently gets the (wrong) global mask ORed in instead of a table lookup.
+    // Hard to hit (only on a region-mask miss or an all-zero compressed
+    // window) and pre-existing, but scoped
+    // here since this function owns the tagging.
+    fn list_ptr<const LIST_ONLY: bool>(ptr: usize) -> NonZeroU32 {
+        let idx = ptr_list().list(ptr);
+        if LIST_ONLY {
+            idx
+        } else {
+            unsafe { NonZeroU32::new_unchecked((idx.get() << 1) | 1) }
         }
     }
 
-    fn unlist_ptr<const CMPS_LEVEL: i32>(ptr: u32) {
+    fn unlist_ptr<const CMPS_LEVEL: i32>(ptr: NonZeroU32) {
         if CMPS_LEVEL == 0 {
-            ptr_list()[(ptr as usize) - 1] = 0;
+            ptr_list().unlist(ptr);
         } else if CMPS_LEVEL > 0 {
-            let p = ptr as usize;
+            let p = ptr.get() as usize;
             if listed(p) {
-                ptr_list()[(p >> 1) - 1] = 0;
+                ptr_list().unlist(unsafe { NonZeroU32::new_unchecked((p >> 1) as u32) });
             }
         }
     }
@@ -75,14 +186,14 @@ pub mod cmpsptr {
     #[inline(always)]
     const fn cmps_level<const CMPS_LEVEL: i32>() -> u32 {
         if CMPS_LEVEL < 1 {
-            CMPS_LEVEL.abs() as u32
+            CMPS_LEVEL.unsigned_abs()
         } else {
             (CMPS_LEVEL - 1) as u32
         }
     }
 
     #[inline(always)]
-    fn check_global_mask<const CMPS_LEVEL: i32, const NEW_ALLOC: bool>(ptr: usize) -> u32 {
+    fn check_global_mask<const CMPS_LEVEL: i32, const NEW_ALLOC: bool>(ptr: usize) -> NonZeroU32 {
         if CMPS_LEVEL == 0 {
             list_ptr::<true>(ptr)
         } else if NEW_ALLOC {
@@ -91,92 +202,120 @@ pub mod cmpsptr {
             global_compress::<CMPS_LEVEL>(ptr)
         }
     }
-    
+
     macro_rules! compress {
-        ($mask: ident, $ptr: ident) => {
+        ($mask: expr, $ptr: ident) => {
             {
                 let shift_bits = 32 + cmps_level::<CMPS_LEVEL>();
-                unsafe {
-                    if $mask == usize::MAX {
-                        $mask = ($ptr >> shift_bits) << shift_bits;
-                        ($ptr >> cmps_level::<CMPS_LEVEL>()) as u32
-                    } else {
-                        if $mask == ($ptr >> shift_bits) << shift_bits {
-                            ($ptr >> cmps_level::<CMPS_LEVEL>()) as u32
-                        } else {
-                            if CMPS_LEVEL < 0 {
-                                panic!("CANNOT COMPRESS POINTER {}!", $ptr)
-                            } else {
-                                list_ptr::<false>($ptr)
-                            }
-                        }
+                let high_bits = ($ptr >> shift_bits) << shift_bits;
+                // Either we're the first to establish the region mask (the exchange
+                // succeeds) or some thread already established one (the exchange
+                // fails and hands back whatever is there); either way `established`
+                // is the mask now in effect, with no lost update between them.
+                let established = match $mask.compare_exchange(
+                    usize::MAX, high_bits, Ordering::AcqRel, Ordering::Acquire,
+                ) {
+                    Ok(_) => high_bits,
+                    Err(existing) => existing,
+                };
+                if established == high_bits {
+                    match NonZeroU32::new(($ptr >> cmps_level::<CMPS_LEVEL>()) as u32) {
+                        Some(compressed) => compressed,
+                        // A legitimate address whose bits happen to be all zero in
+                        // this window still needs a non-zero `NonZeroU32` to store;
+                        // fall back to listing it, same as a mask mismatch below,
+                        // instead of manufacturing a value (or panicking) for an
+                        // otherwise perfectly valid pointer.
+                        None if CMPS_LEVEL > 0 => list_ptr::<false>($ptr),
+                        None => panic!("CANNOT COMPRESS POINTER {}!", $ptr),
                     }
+                } else if CMPS_LEVEL < 0 {
+                    panic!("CANNOT COMPRESS POINTER {}!", $ptr)
+                } else {
+                    list_ptr::<false>($ptr)
                 }
             }
         }
     }
 
-    fn global_compress_new<const CMPS_LEVEL: i32>(ptr: usize) -> u32 {
+    fn global_compress_new<const CMPS_LEVEL: i32>(ptr: usize) -> NonZeroU32 {
         compress!(_GLOBAL_NEW_MASK, ptr)
     }
 
-    fn global_compress<const CMPS_LEVEL: i32>(ptr: usize) -> u32 {
+    fn global_compress<const CMPS_LEVEL: i32>(ptr: usize) -> NonZeroU32 {
         compress!(_GLOBAL_MASK, ptr)
     }
-    
+
     #[inline(always)]
     fn apply_global_mask<const NEW_ALLOC: bool, const CMPS_LEVEL: i32>(ptr: usize) -> usize {
-        unsafe {
-            if CMPS_LEVEL == 0 {                
-                ptr_list()[ptr - 1]
-            } else if CMPS_LEVEL > 0 && listed(ptr) {
-                ptr_list()[(ptr >> 1) - 1]
-            } else if NEW_ALLOC {
-                ptr | _GLOBAL_NEW_MASK
-            } else {
-                ptr | _GLOBAL_MASK
-            }
+        if CMPS_LEVEL == 0 {
+            ptr_list().slots[ptr - 1]
+        } else if CMPS_LEVEL > 0 && listed(ptr) {
+            ptr_list().slots[(ptr >> 1) - 1]
+        } else if NEW_ALLOC {
+            ptr | _GLOBAL_NEW_MASK.load(Ordering::Acquire)
+        } else {
+            ptr | _GLOBAL_MASK.load(Ordering::Acquire)
         }
     }
 
     pub trait Counter {
-        fn increase_count(&mut self) -> usize;
-        fn decrease_count(&mut self) -> usize;
+        fn increase_count(&self) -> usize;
+        fn decrease_count(&self) -> usize;
         fn current_count(&self) -> usize;
         fn reset_count(&mut self);
+        // Increments unless the count is already zero, atomically w.r.t. a
+        // concurrent `decrease_count`; returns whether it happened. Backs
+        // `Weak::upgrade`-style revival, where a plain load-then-increment
+        // would race a drop that frees the object between the two steps.
+        fn increase_if_nonzero(&self) -> bool;
     }
 
-    impl Counter for u32 {
+    // `Cell<u32>` is the single-threaded, non-atomic counterpart to `AtomicU32`
+    // below: interior mutability through `&self` without needing atomics.
+    impl Counter for Cell<u32> {
 
-        fn increase_count(&mut self) -> usize {
-            let cnt = *self + 1;
-            *self = cnt;
+        fn increase_count(&self) -> usize {
+            let cnt = self.get() + 1;
+            self.set(cnt);
             cnt as usize
         }
 
-        fn decrease_count(&mut self) -> usize {
-            let cnt = *self - 1;
-            *self = cnt;
+        fn decrease_count(&self) -> usize {
+            let cnt = self.get() - 1;
+            self.set(cnt);
             cnt as usize
         }
 
         fn current_count(&self) -> usize {
-            *self as usize
+            self.get() as usize
         }
 
         fn reset_count(&mut self) {
-            (*self) = 1;
+            self.set(1);
+        }
+
+        fn increase_if_nonzero(&self) -> bool {
+            let cnt = self.get();
+            if cnt == 0 {
+                false
+            } else {
+                self.set(cnt + 1);
+                true
+            }
         }
     }
 
     impl Counter for AtomicU32 {
 
-        fn increase_count(&mut self) -> usize {
-            (*self).fetch_add(1, Ordering::SeqCst) as usize
+        fn increase_count(&self) -> usize {
+            self.fetch_add(1, Ordering::Relaxed) as usize
         }
 
-        fn decrease_count(&mut self) -> usize {
-            (*self).fetch_min(1, Ordering::SeqCst) as usize
+        fn decrease_count(&self) -> usize {
+            // `fetch_sub` hands back the pre-decrement value; every call site
+            // compares against the post-decrement count, so subtract 1 here.
+            (self.fetch_sub(1, Ordering::Release) - 1) as usize
         }
 
         fn current_count(&self) -> usize {
@@ -186,20 +325,33 @@ pub mod cmpsptr {
         fn reset_count(&mut self) {
             (*self).store(1, Ordering::SeqCst);
         }
+
+        fn increase_if_nonzero(&self) -> bool {
+            let mut cnt = self.load(Ordering::Relaxed);
+            loop {
+                if cnt == 0 {
+                    return false;
+                }
+                match self.compare_exchange_weak(cnt, cnt + 1, Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => return true,
+                    Err(actual) => cnt = actual,
+                }
+            }
+        }
     }
 
     //#[derive(Copy, Clone)]
     pub struct CmpsPtr<'a, T: 'a, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> {
         _phantom: PhantomData<&'a T>,
-        _ptr: u32
+        _ptr: NonZeroU32
     }
 
     impl<T, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> CmpsPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> {
         #[inline(always)]
         fn get_ptr(&self) -> usize {
-            apply_global_mask::<NEW_ALLOC, CMPS_LEVEL>((self._ptr  as usize) << cmps_level::<CMPS_LEVEL>())
+            apply_global_mask::<NEW_ALLOC, CMPS_LEVEL>((self._ptr.get() as usize) << cmps_level::<CMPS_LEVEL>())
         }
-        
+
         #[inline(always)]
         pub fn ptr(&self) -> &T {
             unsafe {
@@ -215,7 +367,7 @@ pub mod cmpsptr {
         }
 
         #[inline(always)]
-        fn compress(ptr: &mut T) -> u32 {
+        fn compress(ptr: &mut T) -> NonZeroU32 {
             let p = (ptr as *mut T) as usize;
             check_global_mask::<CMPS_LEVEL, NEW_ALLOC>(p)
         }
@@ -238,7 +390,7 @@ pub mod cmpsptr {
 
         #[inline(always)]
         pub fn new(ptr: &mut T) -> CmpsPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> {
-            if CMPS_LEVEL > 3 || CMPS_LEVEL < -3 {                
+            if CMPS_LEVEL > 3 || CMPS_LEVEL < -3 {
                 panic!("A COMPRESSION LEVEL HIGHER THAN 3 IS NOT SUPPORTED!")
             }
             CmpsPtr::<'_, T, CMPS_LEVEL, NEW_ALLOC> {
@@ -247,14 +399,6 @@ pub mod cmpsptr {
             }
         }
 
-        #[inline(always)]
-        fn new_copy<'a>(ptr: u32) -> CmpsPtr<'a, T, CMPS_LEVEL, NEW_ALLOC> {
-            CmpsPtr::<'a, T, CMPS_LEVEL, NEW_ALLOC> {
-                _phantom: PhantomData,
-                _ptr: ptr
-            }
-        }
-
     }
 
     impl<T, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> Copy for CmpsPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> {}
@@ -262,7 +406,7 @@ pub mod cmpsptr {
     impl<'a, T, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> Clone for CmpsPtr<'a, T, CMPS_LEVEL, NEW_ALLOC> {
         #[inline(always)]
         fn clone(&self) -> CmpsPtr<'a, T, CMPS_LEVEL, NEW_ALLOC> {
-            CmpsPtr::<'a, T, CMPS_LEVEL, NEW_ALLOC>::new_copy(self._ptr)
+            *self
         }
     }
 
@@ -275,7 +419,79 @@ pub mod cmpsptr {
     }
 
     impl<T, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> DerefMut for CmpsPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> {
-        #[inline(always)]    
+        #[inline(always)]
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            self.ptr_mut()
+        }
+    }
+
+    // DST-capable counterpart to `CmpsPtr`: the compressed 32-bit data address
+    // still lives in `_ptr`, but a fat pointer (`[U]`, `dyn Trait`) also needs its
+    // metadata (slice length / vtable pointer), which lives alongside it here.
+    pub struct CmpsFatPtr<'a, T: ?Sized + 'a, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> {
+        _phantom: PhantomData<&'a T>,
+        _ptr: NonZeroU32,
+        _meta: <T as Pointee>::Metadata
+    }
+
+    impl<T: ?Sized, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> CmpsFatPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> {
+        #[inline(always)]
+        fn get_ptr(&self) -> usize {
+            apply_global_mask::<NEW_ALLOC, CMPS_LEVEL>((self._ptr.get() as usize) << cmps_level::<CMPS_LEVEL>())
+        }
+
+        #[inline(always)]
+        pub fn ptr(&self) -> &T {
+            unsafe {
+                &*ptr::from_raw_parts(self.get_ptr() as *const (), self._meta)
+            }
+        }
+
+        #[inline(always)]
+        pub fn ptr_mut(&self) -> &mut T {
+            unsafe {
+                &mut *ptr::from_raw_parts_mut(self.get_ptr() as *mut (), self._meta)
+            }
+        }
+
+        #[inline(always)]
+        fn compress(ptr: &mut T) -> NonZeroU32 {
+            let p = (ptr as *mut T) as *mut () as usize;
+            check_global_mask::<CMPS_LEVEL, NEW_ALLOC>(p)
+        }
+
+        pub fn new(ptr: &mut T) -> CmpsFatPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> {
+            if CMPS_LEVEL > 3 || CMPS_LEVEL < -3 {
+                panic!("A COMPRESSION LEVEL HIGHER THAN 3 IS NOT SUPPORTED!")
+            }
+            let meta = ptr::metadata(ptr as *const T);
+            CmpsFatPtr::<'_, T, CMPS_LEVEL, NEW_ALLOC> {
+                _ptr: CmpsFatPtr::<'_, T, CMPS_LEVEL, NEW_ALLOC>::compress(ptr),
+                _meta: meta,
+                _phantom: PhantomData
+            }
+        }
+    }
+
+    impl<T: ?Sized, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> Copy for CmpsFatPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> where <T as Pointee>::Metadata: Copy {}
+
+    impl<'a, T: ?Sized, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> Clone for CmpsFatPtr<'a, T, CMPS_LEVEL, NEW_ALLOC> where <T as Pointee>::Metadata: Copy {
+        #[inline(always)]
+        fn clone(&self) -> CmpsFatPtr<'a, T, CMPS_LEVEL, NEW_ALLOC> {
+            *self
+        }
+    }
+
+    impl<T: ?Sized, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> Deref for CmpsFatPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> {
+        type Target = T;
+        #[inline(always)]
+        fn deref(&self) -> &Self::Target {
+            self.ptr()
+        }
+    }
+
+    impl<T: ?Sized, const CMPS_LEVEL: i32, const NEW_ALLOC: bool> DerefMut for CmpsFatPtr<'_, T, CMPS_LEVEL, NEW_ALLOC> {
+        #[inline(always)]
         fn deref_mut(&mut self) -> &mut Self::Target {
             self.ptr_mut()
         }
@@ -286,8 +502,8 @@ pub mod cmpsptr {
     }
 
     impl<'a, T, const CMPS_LEVEL: i32> CmpsRef<'a, T, CMPS_LEVEL> {
-        #[inline(always)]    
-        pub fn new(ptr: &'a mut T) -> CmpsRef<'_, T, CMPS_LEVEL> {
+        #[inline(always)]
+        pub fn new(ptr: &'a mut T) -> CmpsRef<'a, T, CMPS_LEVEL> {
             CmpsRef::<'a, T, CMPS_LEVEL> {
                 _ptr: CmpsPtr::<'a, T, CMPS_LEVEL, false>::new(ptr),
             }
@@ -360,6 +576,117 @@ pub mod cmpsptr {
         }
     }
 
+    // Unsized counterpart to `CmpsUnq`, for `[U]` and `dyn Trait` owning pointers:
+    // the backing allocation is sized from the fat pointer being stored rather
+    // than from `Layout::new::<T>()`, since `T` no longer has a static size.
+    pub struct CmpsUnqDst<'a, T: ?Sized + 'a, const CMPS_LEVEL: i32> {
+        _ptr: CmpsFatPtr<'a, T, CMPS_LEVEL, true>
+    }
+
+    impl<'a, T: ?Sized, const CMPS_LEVEL: i32> CmpsUnqDst<'a, T, CMPS_LEVEL> {
+        // Coerces a concrete `U` (e.g. a struct implementing `T`, or `[V; N]` when
+        // `T = [V]`) into the stored `dyn`/slice form, capturing its vtable or
+        // length, and moves its bytes onto a fresh heap allocation sized to fit.
+        // `val` is wrapped in `ManuallyDrop` so ownership passes to the new
+        // allocation instead of also running `U`'s destructor on the caller's copy.
+        pub fn new_unsize<U: Unsize<T>>(val: U) -> CmpsUnqDst<'a, T, CMPS_LEVEL> {
+            let mut val = ManuallyDrop::new(val);
+            let wide: &mut T = &mut *val;
+            let layout = Layout::for_value(wide);
+            unsafe {
+                // A zero-sized `U` (e.g. a stateless struct coerced to `dyn Trait`)
+                // gives a zero-size layout, which `alloc` must never see; use a
+                // dangling, well-aligned pointer instead, same as `new_slice(0)`.
+                let raw = if layout.size() == 0 {
+                    NonNull::<U>::dangling().as_ptr() as *mut u8
+                } else {
+                    let raw = alloc(layout);
+                    if raw.is_null() {
+                        handle_alloc_error(layout);
+                    }
+                    raw
+                };
+                let meta = ptr::metadata(wide as *const T);
+                copy_nonoverlapping((wide as *mut T) as *mut u8, raw, layout.size());
+                let fat: &mut T = &mut *ptr::from_raw_parts_mut(raw as *mut (), meta);
+                CmpsUnqDst::<'a, T, CMPS_LEVEL> {
+                    _ptr: CmpsFatPtr::<'a, T, CMPS_LEVEL, true>::new(fat)
+                }
+            }
+        }
+
+        #[inline(always)]
+        pub fn ptr_mut(&self) -> &mut T {
+            self._ptr.ptr_mut()
+        }
+
+        #[inline(always)]
+        pub fn ptr(&self) -> &T {
+            self._ptr.ptr()
+        }
+
+    }
+
+    impl<'a, U: Copy, const CMPS_LEVEL: i32> CmpsUnqDst<'a, [U], CMPS_LEVEL> {
+        // Allocates `len` contiguous, uninitialized elements and stores the
+        // length as the slice's metadata. `Drop` below still runs `drop_in_place`
+        // over the whole slice, so `U` is restricted to `Copy` (which cannot have
+        // a destructor, so that pass over uninitialized bytes is a no-op) rather
+        // than leaving elements half-alive for some other `U::drop` to trip on.
+        // A zero-size slice (`len == 0`, or a zero-sized `U`) skips `alloc`
+        // entirely and uses a dangling, well-aligned pointer instead, since a
+        // zero-size `Layout` is not a valid `alloc` call.
+        pub fn new_slice(len: usize) -> CmpsUnqDst<'a, [U], CMPS_LEVEL> {
+            unsafe {
+                let raw = if len == 0 || core::mem::size_of::<U>() == 0 {
+                    NonNull::<U>::dangling().as_ptr()
+                } else {
+                    let layout = Layout::array::<U>(len).unwrap_or_else(|_| panic!("INVALID SLICE LAYOUT"));
+                    let raw = alloc(layout);
+                    if raw.is_null() {
+                        handle_alloc_error(layout);
+                    }
+                    raw as *mut U
+                };
+                let fat: &mut [U] = &mut *ptr::from_raw_parts_mut(raw as *mut (), len);
+                CmpsUnqDst::<'a, [U], CMPS_LEVEL> {
+                    _ptr: CmpsFatPtr::<'a, [U], CMPS_LEVEL, true>::new(fat)
+                }
+            }
+        }
+    }
+
+    impl<T: ?Sized, const CMPS_LEVEL: i32> Deref for CmpsUnqDst<'_, T, CMPS_LEVEL> {
+        type Target = T;
+        #[inline(always)]
+        fn deref(&self) -> &Self::Target {
+            self.ptr()
+        }
+    }
+
+    impl<T: ?Sized, const CMPS_LEVEL: i32> DerefMut for CmpsUnqDst<'_, T, CMPS_LEVEL> {
+        #[inline(always)]
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            self.ptr_mut()
+        }
+    }
+
+    impl<T: ?Sized, const CMPS_LEVEL: i32> Drop for CmpsUnqDst<'_, T, CMPS_LEVEL> {
+        #[inline(always)]
+        fn drop(&mut self) {
+            let layout = Layout::for_value(self.ptr());
+            unsafe {
+                unlist_ptr::<CMPS_LEVEL>(self._ptr._ptr);
+                drop_in_place(self.ptr_mut() as *mut T);
+                // A zero-size layout (e.g. `new_slice(0)`) was never actually
+                // passed to `alloc`, so it must not be passed to `dealloc` either.
+                if layout.size() != 0 {
+                    dealloc((self.ptr_mut() as *mut T) as *mut u8, layout);
+                }
+            }
+        }
+    }
+
     pub struct CmpsCnt<'a, T: 'a, const COW: bool, const CMPS_LEVEL: i32> where T: Counter {
         _ptr: CmpsPtr<'a, T, CMPS_LEVEL, true>
     }
@@ -421,6 +748,9 @@ pub mod cmpsptr {
         #[inline(always)]
         fn drop(&mut self) {
             if self.decrease_count() == 0 {
+                // Pairs with the `Release` decrement so every prior mutation through
+                // this (or a cloned) handle is visible before we free the object.
+                fence(Ordering::Acquire);
                 let obj_layout = Layout::new::<T>();
                 unsafe {
                     unlist_ptr::<CMPS_LEVEL>(self._ptr._ptr);
@@ -433,25 +763,186 @@ pub mod cmpsptr {
     impl<'a, T, const COW: bool, const CMPS_LEVEL: i32> Clone for CmpsCnt<'a, T, COW, CMPS_LEVEL> where T: Counter {
         #[inline(always)]
         fn clone(&self) -> CmpsCnt<'a, T, COW, CMPS_LEVEL> {
+            self._ptr.ptr().increase_count();
+            CmpsCnt::<'a, T, COW, CMPS_LEVEL> {
+                _ptr: self._ptr
+            }
+        }
+    }
+
+    // Unsized counterpart to `CmpsCnt`: only meaningful for `dyn Trait`, since the
+    // count field that backs `Counter` has to live inside `T` itself, which rules
+    // out slices. Allocation is sized from the fat pointer via `Layout::for_value`
+    // rather than `Layout::new::<T>()`.
+    pub struct CmpsCntDst<'a, T: ?Sized + 'a, const COW: bool, const CMPS_LEVEL: i32> where T: Counter {
+        _ptr: CmpsFatPtr<'a, T, CMPS_LEVEL, true>
+    }
+
+    impl<'a, T: ?Sized, const COW: bool, const CMPS_LEVEL: i32> CmpsCntDst<'a, T, COW, CMPS_LEVEL> where T: Counter {
+
+        // Coerces a concrete `U: Counter` into the stored `dyn Trait` form,
+        // capturing its vtable, and moves its bytes onto a fresh allocation sized
+        // to fit; see `CmpsUnqDst::new_unsize` for why `val` is wrapped in
+        // `ManuallyDrop`.
+        pub fn new_unsize<U: Unsize<T>>(val: U) -> CmpsCntDst<'a, T, COW, CMPS_LEVEL> {
+            let mut val = ManuallyDrop::new(val);
+            let wide: &mut T = &mut *val;
+            let layout = Layout::for_value(wide);
             unsafe {
-                (*((self as *const Self) as *mut Self)).increase_count();
+                // See `CmpsUnqDst::new_unsize` for why a zero-size layout can't
+                // be passed to `alloc`.
+                let raw = if layout.size() == 0 {
+                    NonNull::<U>::dangling().as_ptr() as *mut u8
+                } else {
+                    let raw = alloc(layout);
+                    if raw.is_null() {
+                        handle_alloc_error(layout);
+                    }
+                    raw
+                };
+                let meta = ptr::metadata(wide as *const T);
+                copy_nonoverlapping((wide as *mut T) as *mut u8, raw, layout.size());
+                let fat: &mut T = &mut *ptr::from_raw_parts_mut(raw as *mut (), meta);
+                fat.reset_count();
+                CmpsCntDst::<'a, T, COW, CMPS_LEVEL> {
+                    _ptr: CmpsFatPtr::<'a, T, CMPS_LEVEL, true>::new(fat)
+                }
             }
-            CmpsCnt::<'a, T, COW, CMPS_LEVEL> {
+        }
+
+        pub fn detach(&mut self) {
+            if self.current_count() > 1 {
+                let layout = Layout::for_value(self._ptr.ptr());
+                unsafe {
+                    let raw = alloc(layout);
+                    if raw.is_null() {
+                        handle_alloc_error(layout);
+                    }
+                    let meta = ptr::metadata(self._ptr.ptr() as *const T);
+                    copy_nonoverlapping((self._ptr.ptr_mut() as *mut T) as *mut u8, raw, layout.size());
+                    let fat: &mut T = &mut *ptr::from_raw_parts_mut(raw as *mut (), meta);
+                    fat.reset_count();
+                    self.decrease_count();
+                    self._ptr = CmpsFatPtr::<'a, T, CMPS_LEVEL, true>::new(fat);
+                }
+            }
+        }
+
+        #[inline(always)]
+        pub fn ptr_mut(&mut self) -> &mut T {
+            if COW {
+                self.detach();
+            }
+            self._ptr.ptr_mut()
+        }
+
+        #[inline(always)]
+        pub fn ptr(&self) -> &T {
+            self._ptr.ptr()
+        }
+
+    }
+
+    impl<T: ?Sized, const COW: bool, const CMPS_LEVEL: i32> Deref for CmpsCntDst<'_, T, COW, CMPS_LEVEL> where T: Counter {
+        type Target = T;
+        #[inline(always)]
+        fn deref(&self) -> &Self::Target {
+            self.ptr()
+        }
+    }
+
+    impl<T: ?Sized, const COW: bool, const CMPS_LEVEL: i32> DerefMut for CmpsCntDst<'_, T, COW, CMPS_LEVEL> where T: Counter {
+        #[inline(always)]
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            self.ptr_mut()
+        }
+    }
+
+    impl<T: ?Sized, const COW: bool, const CMPS_LEVEL: i32> Drop for CmpsCntDst<'_, T, COW, CMPS_LEVEL> where T: Counter {
+        #[inline(always)]
+        fn drop(&mut self) {
+            if self.decrease_count() == 0 {
+                // See CmpsCnt::drop for why this fence is here.
+                fence(Ordering::Acquire);
+                let layout = Layout::for_value(self._ptr.ptr());
+                unsafe {
+                    unlist_ptr::<CMPS_LEVEL>(self._ptr._ptr);
+                    drop_in_place(self._ptr.ptr_mut() as *mut T);
+                    if layout.size() != 0 {
+                        dealloc((self._ptr.ptr_mut() as *mut T) as *mut u8, layout);
+                    }
+                }
+            }
+        }
+    }
+
+    impl<'a, T: ?Sized, const COW: bool, const CMPS_LEVEL: i32> Clone for CmpsCntDst<'a, T, COW, CMPS_LEVEL> where T: Counter {
+        #[inline(always)]
+        fn clone(&self) -> CmpsCntDst<'a, T, COW, CMPS_LEVEL> {
+            self._ptr.ptr().increase_count();
+            CmpsCntDst::<'a, T, COW, CMPS_LEVEL> {
                 _ptr: self._ptr
             }
         }
     }
 
+    // Backing block for a `CmpsShr`'s external counter: a strong count and a weak
+    // count side by side, so weak handles can outlive the pointee. The weak count
+    // carries an implicit +1 for as long as any strong reference exists, mirroring
+    // how `alloc::sync::Arc`/`Weak` share their single `ArcInner` allocation.
+    pub struct CmpsRfc<C> where C: Counter {
+        _strong: C,
+        _weak: C
+    }
+
+    impl<C> CmpsRfc<C> where C: Counter {
+        #[inline(always)]
+        fn reset(&mut self) {
+            self._strong.reset_count();
+            self._weak.reset_count();
+        }
+
+        #[inline(always)]
+        fn increase_strong(&self) -> usize {
+            self._strong.increase_count()
+        }
+
+        #[inline(always)]
+        fn decrease_strong(&self) -> usize {
+            self._strong.decrease_count()
+        }
+
+        #[inline(always)]
+        fn current_strong(&self) -> usize {
+            self._strong.current_count()
+        }
+
+        #[inline(always)]
+        fn increase_strong_if_nonzero(&self) -> bool {
+            self._strong.increase_if_nonzero()
+        }
+
+        #[inline(always)]
+        fn increase_weak(&self) -> usize {
+            self._weak.increase_count()
+        }
+
+        #[inline(always)]
+        fn decrease_weak(&self) -> usize {
+            self._weak.decrease_count()
+        }
+    }
+
     pub struct CmpsShr<'a, T: 'a, C: 'a, const COW: bool, const CMPS_LEVEL: i32> where C: Counter {
         _ptr: CmpsPtr<'a, T, CMPS_LEVEL, true>,
-        _rfc: CmpsPtr<'a, C, 3, true>
+        _rfc: CmpsPtr<'a, CmpsRfc<C>, 3, true>
     }
 
     impl<'a, T, C, const COW: bool, const CMPS_LEVEL: i32> CmpsShr<'a, T, C, COW, CMPS_LEVEL> where C: Counter {
 
         pub fn new() -> CmpsShr<'a, T, C, COW, CMPS_LEVEL> {
-            let mut rfc = CmpsPtr::<'a, C, 3, true>::new_alloc();
-            rfc.reset_count();
+            let mut rfc = CmpsPtr::<'a, CmpsRfc<C>, 3, true>::new_alloc();
+            rfc.reset();
             CmpsShr::<'a, T, C, COW, CMPS_LEVEL> {
                 _ptr: CmpsPtr::<'a, T, CMPS_LEVEL, true>::new_alloc(),
                 _rfc: rfc
@@ -459,16 +950,16 @@ pub mod cmpsptr {
         }
 
         pub fn detach(&mut self) {
-            if self._rfc.current_count() > 1 {
+            if self._rfc.current_strong() > 1 {
                 let ptr = CmpsPtr::<'a, T, CMPS_LEVEL, true>::new_alloc();
                 let layout = Layout::new::<T>();
                 unsafe {
                     copy_nonoverlapping(self._ptr.ptr_mut() as *mut T, ptr.ptr_mut() as *mut T, layout.size());
                 }
                 self._ptr = ptr;
-                self._rfc.decrease_count();
-                let mut rfc = CmpsPtr::<'a, C, 3, true>::new_alloc();
-                rfc.reset_count();
+                self._rfc.decrease_strong();
+                let mut rfc = CmpsPtr::<'a, CmpsRfc<C>, 3, true>::new_alloc();
+                rfc.reset();
                 self._rfc = rfc;
             }
         }
@@ -486,8 +977,27 @@ pub mod cmpsptr {
             self._ptr.ptr()
         }
 
+        // Non-owning handle: bumps only the weak count, so cyclic graphs and
+        // observer patterns don't keep the pointee alive.
+        pub fn downgrade(&self) -> CmpsWeak<'a, T, C, COW, CMPS_LEVEL> {
+            self._rfc.ptr().increase_weak();
+            CmpsWeak::<'a, T, C, COW, CMPS_LEVEL> {
+                _ptr: self._ptr,
+                _rfc: self._rfc
+            }
+        }
+
     }
 
+    // Only COW = false is Send/Sync: detach()'s "current_strong() > 1, then
+    // decrease_strong()" is two separate steps, and a concurrent drop between
+    // them can make decrease_strong() return 0 while detach() ignores it,
+    // leaking the old allocation. Fixed at `false` (rather than generic over
+    // COW) keeps that race confined to single-threaded use instead of needing
+    // a CAS-based detach.
+    unsafe impl<'a, T, const CMPS_LEVEL: i32> Send for CmpsShr<'a, T, AtomicU32, false, CMPS_LEVEL> where T: Send + Sync {}
+    unsafe impl<'a, T, const CMPS_LEVEL: i32> Sync for CmpsShr<'a, T, AtomicU32, false, CMPS_LEVEL> where T: Send + Sync {}
+
     impl<T, C, const COW: bool, const CMPS_LEVEL: i32> Deref for CmpsShr<'_, T, C, COW, CMPS_LEVEL> where C: Counter {
         type Target = T;
         #[inline(always)]
@@ -506,13 +1016,22 @@ pub mod cmpsptr {
     impl<T, C, const COW: bool, const CMPS_LEVEL: i32> Drop for CmpsShr<'_, T, C, COW, CMPS_LEVEL> where C: Counter {
         #[inline(always)]
         fn drop(&mut self) {
-            if self._rfc.decrease_count() == 0 {
+            if self._rfc.decrease_strong() == 0 {
+                // See CmpsCnt::drop for why this fence is here.
+                fence(Ordering::Acquire);
                 let obj_layout = Layout::new::<T>();
-                let cnt_layout = Layout::new::<u32>();
-                unsafe {                    
+                unsafe {
                     unlist_ptr::<CMPS_LEVEL>(self._ptr._ptr);
+                    drop_in_place(self.ptr_mut() as *mut T);
                     dealloc((self.ptr_mut() as *mut T) as *mut u8, obj_layout);
-                    dealloc((self._rfc.ptr_mut() as *mut C) as *mut u8, cnt_layout);
+                }
+                // The counter block stays alive until every weak handle is gone too.
+                if self._rfc.decrease_weak() == 0 {
+                    fence(Ordering::Acquire);
+                    let cnt_layout = Layout::new::<CmpsRfc<C>>();
+                    unsafe {
+                        dealloc((self._rfc.ptr_mut() as *mut CmpsRfc<C>) as *mut u8, cnt_layout);
+                    }
                 }
             }
         }
@@ -521,9 +1040,7 @@ pub mod cmpsptr {
     impl<'a, T, C, const COW: bool, const CMPS_LEVEL: i32> Clone for CmpsShr<'a, T, C, COW, CMPS_LEVEL> where C: Counter {
         #[inline(always)]
         fn clone(&self) -> CmpsShr<'a, T, C, COW, CMPS_LEVEL> {
-            unsafe {
-                (*((self as *const Self) as *mut Self))._rfc.increase_count();
-            }
+            self._rfc.ptr().increase_strong();
             CmpsShr::<'a, T, C, COW, CMPS_LEVEL> {
                 _ptr: self._ptr,
                 _rfc: self._rfc
@@ -531,4 +1048,56 @@ pub mod cmpsptr {
         }
     }
 
-}
\ No newline at end of file
+    pub struct CmpsWeak<'a, T: 'a, C: 'a, const COW: bool, const CMPS_LEVEL: i32> where C: Counter {
+        _ptr: CmpsPtr<'a, T, CMPS_LEVEL, true>,
+        _rfc: CmpsPtr<'a, CmpsRfc<C>, 3, true>
+    }
+
+    impl<'a, T, C, const COW: bool, const CMPS_LEVEL: i32> CmpsWeak<'a, T, C, COW, CMPS_LEVEL> where C: Counter {
+        // Succeeds only while the pointee is still alive, incrementing the strong
+        // count on success so the returned `CmpsShr` shares ownership normally.
+        pub fn upgrade(&self) -> Option<CmpsShr<'a, T, C, COW, CMPS_LEVEL>> {
+            // A plain "load then increment" would race a concurrent drop of the
+            // last `CmpsShr` freeing the object in between; `increase_if_nonzero`
+            // makes the check-and-bump a single atomic step instead.
+            if self._rfc.ptr().increase_strong_if_nonzero() {
+                Some(CmpsShr::<'a, T, C, COW, CMPS_LEVEL> {
+                    _ptr: self._ptr,
+                    _rfc: self._rfc
+                })
+            } else {
+                None
+            }
+        }
+    }
+
+    impl<T, C, const COW: bool, const CMPS_LEVEL: i32> Drop for CmpsWeak<'_, T, C, COW, CMPS_LEVEL> where C: Counter {
+        #[inline(always)]
+        fn drop(&mut self) {
+            if self._rfc.decrease_weak() == 0 {
+                fence(Ordering::Acquire);
+                let cnt_layout = Layout::new::<CmpsRfc<C>>();
+                unsafe {
+                    dealloc((self._rfc.ptr_mut() as *mut CmpsRfc<C>) as *mut u8, cnt_layout);
+                }
+            }
+        }
+    }
+
+    impl<'a, T, C, const COW: bool, const CMPS_LEVEL: i32> Clone for CmpsWeak<'a, T, C, COW, CMPS_LEVEL> where C: Counter {
+        #[inline(always)]
+        fn clone(&self) -> CmpsWeak<'a, T, C, COW, CMPS_LEVEL> {
+            self._rfc.ptr().increase_weak();
+            CmpsWeak::<'a, T, C, COW, CMPS_LEVEL> {
+                _ptr: self._ptr,
+                _rfc: self._rfc
+            }
+        }
+    }
+
+    // Same COW = false restriction as `CmpsShr`, since `upgrade` hands back a
+    // `CmpsShr` with this handle's COW.
+    unsafe impl<'a, T, const CMPS_LEVEL: i32> Send for CmpsWeak<'a, T, AtomicU32, false, CMPS_LEVEL> where T: Send + Sync {}
+    unsafe impl<'a, T, const CMPS_LEVEL: i32> Sync for CmpsWeak<'a, T, AtomicU32, false, CMPS_LEVEL> where T: Send + Sync {}
+
+}
@@ -1,36 +1,57 @@
-mod cmpsptr;
-
-use cmpsptr::cmpsptr::{CmpsRef, CmpsUnq, CmpsRfc, CmpsCnt, CmpsShr, CmpsCow, Counter};
-use std::sync::atomic::{AtomicU32};
+use cmpsptr::cmpsptr::{CmpsRef, CmpsUnq, CmpsCnt, CmpsShr, CmpsUnqDst, Counter};
+use std::cell::Cell;
+use std::sync::atomic::AtomicU32;
 
 use std::mem::size_of;
 
+trait Greeter {
+    fn greet(&self) -> &str;
+}
+
+struct Hello;
+
+impl Greeter for Hello {
+    fn greet(&self) -> &str {
+        "hello"
+    }
+}
+
 struct Test {
     x: i32,
     y: i32,
-    cnt: u32
+    cnt: Cell<u32>
 }
 
 impl Counter for Test {
 
-    fn increase_count(&mut self) -> usize {
-        let cnt = self.cnt + 1;
-        self.cnt = cnt;
+    fn increase_count(&self) -> usize {
+        let cnt = self.cnt.get() + 1;
+        self.cnt.set(cnt);
         cnt as usize
     }
 
-    fn decrease_count(&mut self) -> usize {
-        let cnt = self.cnt - 1;
-        self.cnt = cnt;
+    fn decrease_count(&self) -> usize {
+        let cnt = self.cnt.get() - 1;
+        self.cnt.set(cnt);
         cnt as usize
     }
 
     fn current_count(&self) -> usize {
-        self.cnt as usize
+        self.cnt.get() as usize
     }
 
-    unsafe fn reset_count(&mut self) {
-        self.cnt = 1;
+    fn reset_count(&mut self) {
+        self.cnt.set(1);
+    }
+
+    fn increase_if_nonzero(&self) -> bool {
+        let cnt = self.cnt.get();
+        if cnt == 0 {
+            false
+        } else {
+            self.cnt.set(cnt + 1);
+            true
+        }
     }
 }
 
@@ -39,37 +60,45 @@ impl Clone for Test {
         Test {
             x: self.x,
             y: self.y,
-            cnt: 0
+            cnt: Cell::new(0)
         }
     }
 }
 
 fn main() {
-    unsafe {
-        let mut t = Test { x: 5, y: 9, cnt: 1 };
-        let mut z = Test { x: -3, y: -5, cnt: 1 };
-        let mut u = CmpsUnq::<'_, Test, 3>::new();
-        let mut c = CmpsCow::<CmpsCnt::<'_, Test, 3>, true>::new();
-        let mut s = CmpsShr::<'_, Test, AtomicU32, 3>::new();
-        let mut p = CmpsRef::<'_, Test, 3>::new(&mut t);
-        println!("sizeof p = {}", size_of::<CmpsRef::<Test, 3>>());
-        println!("sizeof c = {}", size_of::<CmpsCnt::<Test, 3>>());
-        println!("sizeof s = {}", size_of::<CmpsShr::<Test, AtomicU32, 3>>());
-        println!("sizeof u = {}", size_of::<CmpsUnq::<Test, 3>>());
-        println!("p.x = {}, p.y = {}", p.x, p.y);
-        p.x = 97;
-        p.y = 53;
-        println!("p.x = {}, p.y = {}", p.x, p.y);
-        p.set_ptr(&mut z);
-        println!("p.x = {}, p.y = {}", p.x, p.y);
-        c.x = 9;
-        c.y = 8;
-        println!("c.x = {}, c.y = {}", c.x, c.y);
-        s.x = -9;
-        s.y = -5;
-        println!("s.x = {}, s.y = {}", s.x, s.y);
-        u.x = 1;
-        u.y = 2;
-        println!("u.x = {}, u.y = {}", u.x, u.y);
-    }
+    let mut t = Test { x: 5, y: 9, cnt: Cell::new(1) };
+    let mut z = Test { x: -3, y: -5, cnt: Cell::new(1) };
+    let mut u = CmpsUnq::<'_, Test, 3>::new();
+    let mut c = CmpsCnt::<'_, Test, true, 3>::new();
+    let mut s = CmpsShr::<'_, Test, AtomicU32, true, 3>::new();
+    let mut p = CmpsRef::<'_, Test, 3>::new(&mut t);
+    println!("sizeof p = {}", size_of::<CmpsRef::<Test, 3>>());
+    println!("sizeof c = {}", size_of::<CmpsCnt::<Test, true, 3>>());
+    println!("sizeof s = {}", size_of::<CmpsShr::<Test, AtomicU32, true, 3>>());
+    println!("sizeof u = {}", size_of::<CmpsUnq::<Test, 3>>());
+    println!("p.x = {}, p.y = {}", p.x, p.y);
+    p.x = 97;
+    p.y = 53;
+    println!("p.x = {}, p.y = {}", p.x, p.y);
+    p.set_ptr(&mut z);
+    println!("p.x = {}, p.y = {}", p.x, p.y);
+    c.x = 9;
+    c.y = 8;
+    println!("c.x = {}, c.y = {}", c.x, c.y);
+    s.x = -9;
+    s.y = -5;
+    println!("s.x = {}, s.y = {}", s.x, s.y);
+    u.x = 1;
+    u.y = 2;
+    println!("u.x = {}, u.y = {}", u.x, u.y);
+
+    let w = s.downgrade();
+    drop(s);
+    println!("w.upgrade().is_none() after drop = {}", w.upgrade().is_none());
+
+    let slice = CmpsUnqDst::<'_, [u8], 3>::new_slice(4);
+    println!("slice.len() = {}", slice.len());
+
+    let greeter = CmpsUnqDst::<'_, dyn Greeter, 3>::new_unsize(Hello);
+    println!("greeter.greet() = {}", greeter.greet());
 }